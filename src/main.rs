@@ -1,11 +1,15 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::io::{self, Write};
 use std::str::FromStr;
 
+mod operations;
+
+use operations::OperationEntry;
+
 #[derive(Parser, Debug)]
 #[command(
     author = "Isabelle Beaudale <izzyabelle@gmail.com>",
@@ -18,22 +22,29 @@ struct Args {
     test: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Group<T> {
     elements: HashSet<T>,
     operation: fn(&T, &T, usize) -> T,
     identity: T,
+    modulus: usize,
 }
 
 impl<T> Group<T>
 where
     T: Eq + Hash + Clone + Debug,
 {
-    fn new(elements: HashSet<T>, operation: fn(&T, &T, usize) -> T, identity: T) -> Result<Self> {
+    fn new(
+        elements: HashSet<T>,
+        operation: fn(&T, &T, usize) -> T,
+        identity: T,
+        modulus: usize,
+    ) -> Result<Self> {
         let group = Group {
             elements,
             operation,
             identity,
+            modulus,
         };
 
         group.is_valid_group()?;
@@ -41,44 +52,281 @@ where
     }
 
     fn is_closed(&self) -> bool {
-        let modulus = self.elements.len();
         self.elements.iter().all(|x| {
-            self.elements
-                .iter()
-                .all(|y| self.elements.contains(&(self.operation)(x, y, modulus)))
+            self.elements.iter().all(|y| {
+                self.elements
+                    .contains(&(self.operation)(x, y, self.modulus))
+            })
         })
     }
 
     fn has_identity(&self) -> bool {
-        let modulus = self.elements.len();
         self.elements.iter().all(|x| {
-            (self.operation)(x, &self.identity, modulus) == *x
-                && (self.operation)(&self.identity, x, modulus) == *x
+            (self.operation)(x, &self.identity, self.modulus) == *x
+                && (self.operation)(&self.identity, x, self.modulus) == *x
         })
     }
 
     fn has_inverses(&self) -> bool {
-        let modulus = self.elements.len();
         self.elements.iter().all(|a| {
             self.elements.iter().any(|b| {
-                (self.operation)(a, b, modulus) == self.identity
-                    && (self.operation)(b, a, modulus) == self.identity
+                (self.operation)(a, b, self.modulus) == self.identity
+                    && (self.operation)(b, a, self.modulus) == self.identity
             })
         })
     }
 
     fn is_associative(&self) -> bool {
-        let modulus = self.elements.len();
         self.elements.iter().all(|a| {
             self.elements.iter().all(|b| {
                 self.elements.iter().all(|c| {
-                    (self.operation)(&(self.operation)(a, b, modulus), c, modulus)
-                        == (self.operation)(a, &(self.operation)(b, c, modulus), modulus)
+                    (self.operation)(&(self.operation)(a, b, self.modulus), c, self.modulus)
+                        == (self.operation)(a, &(self.operation)(b, c, self.modulus), self.modulus)
+                })
+            })
+        })
+    }
+
+    fn cayley_table(&self) -> Vec<Vec<T>>
+    where
+        T: Ord,
+    {
+        let mut ordered: Vec<T> = self.elements.iter().cloned().collect();
+        ordered.sort();
+
+        ordered
+            .iter()
+            .map(|a| {
+                ordered
+                    .iter()
+                    .map(|b| (self.operation)(a, b, self.modulus))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Smallest k>=1 with a^k == identity. Bounded by `elements.len()` since
+    /// an element's order always divides the group order; this also keeps
+    /// the loop from spinning forever if the structure isn't really a group.
+    fn element_order(&self, a: &T) -> usize {
+        let mut current = a.clone();
+        let mut order = 1;
+        while current != self.identity && order <= self.elements.len() {
+            current = (self.operation)(&current, a, self.modulus);
+            order += 1;
+        }
+        order
+    }
+
+    /// Elements whose order equals the group order, i.e. elements that
+    /// generate the whole group.
+    fn generators(&self) -> Vec<T> {
+        let group_order = self.elements.len();
+        self.elements
+            .iter()
+            .filter(|a| self.element_order(a) == group_order)
+            .cloned()
+            .collect()
+    }
+
+    fn is_cyclic(&self) -> bool {
+        !self.generators().is_empty()
+    }
+
+    /// The subgroup generated by a single element: `{identity, a, a^2, ...}`.
+    fn cyclic_subgroup(&self, a: &T) -> HashSet<T> {
+        let mut subgroup = HashSet::new();
+        let mut current = self.identity.clone();
+        subgroup.insert(current.clone());
+        loop {
+            current = (self.operation)(&current, a, self.modulus);
+            if !subgroup.insert(current.clone()) {
+                break;
+            }
+        }
+        subgroup
+    }
+
+    /// Smallest superset of `seed` that is closed under the operation.
+    fn close_under_operation(&self, seed: &HashSet<T>) -> HashSet<T> {
+        let mut closure = seed.clone();
+        loop {
+            let mut additions = Vec::new();
+            for a in &closure {
+                for b in &closure {
+                    let product = (self.operation)(a, b, self.modulus);
+                    if !closure.contains(&product) {
+                        additions.push(product);
+                    }
+                }
+            }
+            if additions.is_empty() {
+                break;
+            }
+            closure.extend(additions);
+        }
+        closure
+    }
+
+    /// Enumerates all subgroups: seed with each element's cyclic subgroup,
+    /// then repeatedly close the union of already-found subgroups under the
+    /// operation until no new subgroup appears.
+    fn subgroups(&self) -> Vec<HashSet<T>> {
+        let mut found: Vec<HashSet<T>> = Vec::new();
+
+        let mut trivial = HashSet::new();
+        trivial.insert(self.identity.clone());
+        found.push(trivial);
+
+        for a in &self.elements {
+            let subgroup = self.cyclic_subgroup(a);
+            if !found.contains(&subgroup) {
+                found.push(subgroup);
+            }
+        }
+
+        if !found.contains(&self.elements) {
+            found.push(self.elements.clone());
+        }
+
+        loop {
+            let mut new_subgroups = Vec::new();
+            for i in 0..found.len() {
+                for j in 0..found.len() {
+                    let union: HashSet<T> = found[i].union(&found[j]).cloned().collect();
+                    let closure = self.close_under_operation(&union);
+                    if !found.contains(&closure) && !new_subgroups.contains(&closure) {
+                        new_subgroups.push(closure);
+                    }
+                }
+            }
+            if new_subgroups.is_empty() {
+                break;
+            }
+            found.extend(new_subgroups);
+        }
+
+        found
+    }
+
+    /// Sorted multiset of element orders, used to cheaply prune non-isomorphic pairs.
+    fn order_multiset(&self) -> Vec<usize> {
+        let mut orders: Vec<usize> = self
+            .elements
+            .iter()
+            .map(|a| self.element_order(a))
+            .collect();
+        orders.sort();
+        orders
+    }
+
+    /// Searches for a structure-preserving bijection to `other` by backtracking:
+    /// identity maps to identity, and the partial map is extended one element at a
+    /// time, requiring `f(a*b) = f(a)*f(b)` against every already-assigned pair
+    /// before recursing. Returns the witness mapping on success.
+    fn is_isomorphic_to(&self, other: &Group<T>) -> Option<HashMap<T, T>> {
+        if self.elements.len() != other.elements.len() {
+            return None;
+        }
+        if self.order_multiset() != other.order_multiset() {
+            return None;
+        }
+
+        let remaining: Vec<T> = self
+            .elements
+            .iter()
+            .filter(|a| **a != self.identity)
+            .cloned()
+            .collect();
+        let codomain_remaining: Vec<T> = other
+            .elements
+            .iter()
+            .filter(|b| **b != other.identity)
+            .cloned()
+            .collect();
+
+        let mut mapping = HashMap::new();
+        mapping.insert(self.identity.clone(), other.identity.clone());
+
+        if self.extend_isomorphism(&remaining, &codomain_remaining, &mut mapping, other) {
+            Some(mapping)
+        } else {
+            None
+        }
+    }
+
+    fn extend_isomorphism(
+        &self,
+        remaining: &[T],
+        codomain_remaining: &[T],
+        mapping: &mut HashMap<T, T>,
+        other: &Group<T>,
+    ) -> bool {
+        let Some(a) = remaining.first() else {
+            return true;
+        };
+        let rest = &remaining[1..];
+
+        for (i, candidate) in codomain_remaining.iter().enumerate() {
+            if mapping.values().any(|mapped| mapped == candidate) {
+                continue;
+            }
+            mapping.insert(a.clone(), candidate.clone());
+
+            let consistent = mapping.iter().all(|(x, fx)| {
+                mapping.iter().all(|(y, fy)| {
+                    let product = (self.operation)(x, y, self.modulus);
+                    match mapping.get(&product) {
+                        Some(mapped_product) => {
+                            *mapped_product == (other.operation)(fx, fy, other.modulus)
+                        }
+                        None => true,
+                    }
                 })
+            });
+
+            if consistent {
+                let mut next_codomain = codomain_remaining.to_vec();
+                next_codomain.remove(i);
+                if self.extend_isomorphism(rest, &next_codomain, mapping, other) {
+                    return true;
+                }
+            }
+
+            mapping.remove(a);
+        }
+
+        false
+    }
+
+    fn is_abelian(&self) -> bool {
+        self.elements.iter().all(|a| {
+            self.elements.iter().all(|b| {
+                (self.operation)(a, b, self.modulus) == (self.operation)(b, a, self.modulus)
             })
         })
     }
 
+    /// Elements that commute with `a`.
+    fn centralizer(&self, a: &T) -> HashSet<T> {
+        self.elements
+            .iter()
+            .filter(|b| {
+                (self.operation)(a, b, self.modulus) == (self.operation)(b, a, self.modulus)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Elements that commute with every element of the group.
+    fn center(&self) -> HashSet<T> {
+        self.elements
+            .iter()
+            .filter(|a| self.centralizer(a).len() == self.elements.len())
+            .cloned()
+            .collect()
+    }
+
     fn is_valid_group(&self) -> Result<()> {
         let mut errors = Vec::new();
 
@@ -125,12 +373,23 @@ const CMD_IDENTITY: &str = "identity";
 const CMD_LIST: &str = "list";
 const CMD_HELP: &str = "help";
 const CMD_CREATE: &str = "create";
+const CMD_OPERATION: &str = "operation";
+const CMD_MODULUS: &str = "modulus";
+const CMD_CAYLEY: &str = "cayley";
+const CMD_ANALYZE: &str = "analyze";
+const CMD_SUBGROUPS: &str = "subgroups";
+const CMD_SAVE: &str = "save";
+const CMD_ISOMORPHIC: &str = "isomorphic";
+const CMD_COMMUTE: &str = "commute";
 const CMD_EXIT: &str = "exit";
 
 fn run() -> Result<()> {
     let mut elements = HashSet::new();
     let mut identity = None;
-    let operation = |a: &i32, b: &i32, m: usize| (a + b) % m as i32;
+    let mut current_operation: &OperationEntry = operations::find("add-mod").unwrap();
+    let mut modulus = None;
+    let mut group: Option<Group<i32>> = None;
+    let mut saved_groups: HashMap<String, Group<i32>> = HashMap::new();
 
     loop {
         let input = user_prompt(MAIN_PROMPT)?;
@@ -171,6 +430,11 @@ fn run() -> Result<()> {
                 } else {
                     eprintln!("Identity element not set.");
                 }
+                println!("Current operation: {}", current_operation.name);
+                println!(
+                    "Current modulus: {} (defaults to element count)",
+                    modulus.unwrap_or(elements.len())
+                );
             }
             CMD_HELP => {
                 println!("Available commands:");
@@ -179,20 +443,150 @@ fn run() -> Result<()> {
                     CMD_ADD
                 );
                 println!("  {} <identity> - Set the identity element", CMD_IDENTITY);
+                println!(
+                    "  {} [name] - Set the operation, or list the choices with no argument",
+                    CMD_OPERATION
+                );
+                println!(
+                    "  {} <n> - Set the modulus passed to the operation (default: element count)",
+                    CMD_MODULUS
+                );
                 println!("  {} - List current elements and identity", CMD_LIST);
                 println!("  {} - Validate and create the group", CMD_CREATE);
+                println!(
+                    "  {} - Print the Cayley table of the created group",
+                    CMD_CAYLEY
+                );
+                println!(
+                    "  {} - Print element orders, generators, and cyclicity",
+                    CMD_ANALYZE
+                );
+                println!(
+                    "  {} - List subgroups and check Lagrange's theorem",
+                    CMD_SUBGROUPS
+                );
+                println!(
+                    "  {} <name> - Save the created group under a name",
+                    CMD_SAVE
+                );
+                println!(
+                    "  {} <g1> <g2> - Check whether two saved groups are isomorphic",
+                    CMD_ISOMORPHIC
+                );
+                println!(
+                    "  {} - Check commutativity and print the center",
+                    CMD_COMMUTE
+                );
                 println!("  {} - Exit the program", CMD_EXIT);
             }
+            CMD_MODULUS => match parts.next().and_then(|arg| usize::from_str(arg).ok()) {
+                Some(0) => {
+                    eprintln!("Modulus must be a positive integer.");
+                }
+                Some(num) => {
+                    modulus = Some(num);
+                    println!("Modulus set.");
+                }
+                None => {
+                    eprintln!("Invalid input, please enter a positive integer.");
+                }
+            },
+            CMD_OPERATION => match parts.next() {
+                Some(name) => match operations::find(name) {
+                    Some(entry) => {
+                        current_operation = entry;
+                        println!("Operation set to '{}'.", entry.name);
+                    }
+                    None => eprintln!(
+                        "Unknown operation '{}'. Type '{}' with no argument to see the list.",
+                        name, CMD_OPERATION
+                    ),
+                },
+                None => {
+                    println!("Available operations:");
+                    for (i, entry) in operations::OPERATIONS.iter().enumerate() {
+                        println!("  {}. {}", i + 1, entry.name);
+                    }
+                    let choice = user_prompt("Select an operation by number or name")?;
+                    let selected = choice
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(1))
+                        .and_then(|i| operations::OPERATIONS.get(i))
+                        .or_else(|| operations::find(&choice));
+                    match selected {
+                        Some(entry) => {
+                            current_operation = entry;
+                            println!("Operation set to '{}'.", entry.name);
+                        }
+                        None => eprintln!("Invalid selection."),
+                    }
+                }
+            },
             CMD_CREATE => {
                 if let Some(identity) = identity {
-                    match Group::new(elements.clone(), operation, identity) {
-                        Ok(group) => println!("Group created: {:?}", group),
-                        Err(e) => eprintln!("Error creating group:\n{}", e),
+                    let modulus = modulus.unwrap_or(elements.len());
+                    match Group::new(
+                        elements.clone(),
+                        current_operation.function,
+                        identity,
+                        modulus,
+                    ) {
+                        Ok(new_group) => {
+                            println!("Group created: {:?}", new_group);
+                            group = Some(new_group);
+                        }
+                        Err(e) => {
+                            eprintln!("Error creating group:\n{}", e);
+                            group = None;
+                        }
                     }
                 } else {
                     eprintln!("Identity element not set.");
                 }
             }
+            CMD_CAYLEY => match &group {
+                Some(group) => print_cayley_table(group),
+                None => eprintln!("No group created yet. Use '{}' first.", CMD_CREATE),
+            },
+            CMD_ANALYZE => match &group {
+                Some(group) => print_analysis(group),
+                None => eprintln!("No group created yet. Use '{}' first.", CMD_CREATE),
+            },
+            CMD_SUBGROUPS => match &group {
+                Some(group) => print_subgroups(group),
+                None => eprintln!("No group created yet. Use '{}' first.", CMD_CREATE),
+            },
+            CMD_SAVE => match (parts.next(), &group) {
+                (Some(name), Some(group)) => {
+                    saved_groups.insert(name.to_string(), group.clone());
+                    println!("Group saved as '{}'.", name);
+                }
+                (None, _) => eprintln!("Usage: {} <name>", CMD_SAVE),
+                (_, None) => eprintln!("No group created yet. Use '{}' first.", CMD_CREATE),
+            },
+            CMD_ISOMORPHIC => match (parts.next(), parts.next()) {
+                (Some(name1), Some(name2)) => {
+                    match (saved_groups.get(name1), saved_groups.get(name2)) {
+                        (Some(g1), Some(g2)) => match g1.is_isomorphic_to(g2) {
+                            Some(mapping) => {
+                                println!("'{}' and '{}' are isomorphic.", name1, name2);
+                                let mut pairs: Vec<(i32, i32)> = mapping.into_iter().collect();
+                                pairs.sort();
+                                println!("Witness mapping: {:?}", pairs);
+                            }
+                            None => println!("'{}' and '{}' are not isomorphic.", name1, name2),
+                        },
+                        (None, _) => eprintln!("No saved group named '{}'.", name1),
+                        (_, None) => eprintln!("No saved group named '{}'.", name2),
+                    }
+                }
+                _ => eprintln!("Usage: {} <g1> <g2>", CMD_ISOMORPHIC),
+            },
+            CMD_COMMUTE => match &group {
+                Some(group) => print_commute(group),
+                None => eprintln!("No group created yet. Use '{}' first.", CMD_CREATE),
+            },
             CMD_EXIT => break,
             _ => println!(
                 "Unknown command. Type '{}' for available commands.",
@@ -204,6 +598,86 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+fn print_cayley_table(group: &Group<i32>) {
+    let mut labels: Vec<i32> = group.elements.iter().cloned().collect();
+    labels.sort();
+    let table = group.cayley_table();
+
+    let width = labels
+        .iter()
+        .map(|label| label.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    print!("{:width$} |", "", width = width);
+    for label in &labels {
+        print!(" {:width$}", label, width = width);
+    }
+    println!();
+    println!("{}", "-".repeat(width + 1 + labels.len() * (width + 1)));
+
+    for (row_label, row) in labels.iter().zip(table.iter()) {
+        print!("{:width$} |", row_label, width = width);
+        for value in row {
+            print!(" {:width$}", value, width = width);
+        }
+        println!();
+    }
+}
+
+fn print_analysis(group: &Group<i32>) {
+    let mut labels: Vec<i32> = group.elements.iter().cloned().collect();
+    labels.sort();
+
+    println!("Element orders:");
+    for label in &labels {
+        println!("  {} has order {}", label, group.element_order(label));
+    }
+
+    let generators = group.generators();
+    println!("Cyclic: {}", group.is_cyclic());
+    if generators.is_empty() {
+        println!("Generators: none");
+    } else {
+        let mut generators = generators;
+        generators.sort();
+        println!("Generators: {:?}", generators);
+    }
+}
+
+fn print_subgroups(group: &Group<i32>) {
+    let mut subgroups = group.subgroups();
+    subgroups.sort_by_key(|s| s.len());
+    let group_order = group.elements.len();
+
+    println!("Subgroups of the order-{} group:", group_order);
+    for subgroup in &subgroups {
+        let mut elements: Vec<i32> = subgroup.iter().cloned().collect();
+        elements.sort();
+        let order = elements.len();
+        if group_order.is_multiple_of(order) {
+            println!("  order {}: {:?}", order, elements);
+        } else {
+            println!(
+                "  order {}: {:?} (violates Lagrange's theorem!)",
+                order, elements
+            );
+        }
+    }
+}
+
+fn print_commute(group: &Group<i32>) {
+    if group.is_abelian() {
+        println!("The group is abelian.");
+        return;
+    }
+
+    println!("The group is not abelian.");
+    let mut center: Vec<i32> = group.center().into_iter().collect();
+    center.sort();
+    println!("Center: {:?}", center);
+}
+
 fn user_prompt(prompt: &str) -> Result<String> {
     println!();
     print!("{}>> ", prompt);
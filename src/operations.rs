@@ -0,0 +1,122 @@
+//! Registry of named binary operations that can be bound to a `Group`.
+//!
+//! Each entry pairs a stable name (used on the REPL's `operation` command)
+//! with a function pointer of the same shape `Group` already expects:
+//! `fn(&i32, &i32, usize) -> i32`, where the third argument is the modulus.
+
+pub type Operation = fn(&i32, &i32, usize) -> i32;
+
+pub struct OperationEntry {
+    pub name: &'static str,
+    pub function: Operation,
+}
+
+pub const OPERATIONS: &[OperationEntry] = &[
+    OperationEntry {
+        name: "add-mod",
+        function: add_mod,
+    },
+    OperationEntry {
+        name: "mul-mod",
+        function: mul_mod,
+    },
+    OperationEntry {
+        name: "xor",
+        function: xor,
+    },
+    OperationEntry {
+        name: "permutation",
+        function: permutation_compose,
+    },
+    OperationEntry {
+        name: "dihedral",
+        function: dihedral_compose,
+    },
+];
+
+/// Looks up a registered operation by name.
+pub fn find(name: &str) -> Option<&'static OperationEntry> {
+    OPERATIONS.iter().find(|entry| entry.name == name)
+}
+
+fn add_mod(a: &i32, b: &i32, m: usize) -> i32 {
+    let sum = *a as i64 + *b as i64;
+    sum.rem_euclid(m as i64) as i32
+}
+
+fn mul_mod(a: &i32, b: &i32, m: usize) -> i32 {
+    let product = *a as i64 * *b as i64;
+    product.rem_euclid(m as i64) as i32
+}
+
+fn xor(a: &i32, b: &i32, m: usize) -> i32 {
+    (a ^ b).rem_euclid(m as i32)
+}
+
+/// Treats elements as indices (in factorial number system) into the
+/// permutations of the smallest `k` with `k! >= modulus`, and composes them.
+fn permutation_compose(a: &i32, b: &i32, modulus: usize) -> i32 {
+    let k = permutation_size_for(modulus);
+    let pa = permutation_from_index(*a as usize, k);
+    let pb = permutation_from_index(*b as usize, k);
+    let composed: Vec<usize> = pb.iter().map(|&i| pa[i]).collect();
+    (index_from_permutation(&composed) % modulus.max(1)) as i32
+}
+
+/// Treats elements `0..n` as rotations and `n..2n` as reflections of a
+/// regular `n`-gon, where `n = modulus / 2`, and composes per the usual
+/// dihedral group relations.
+fn dihedral_compose(a: &i32, b: &i32, modulus: usize) -> i32 {
+    let n = (modulus / 2).max(1) as i32;
+    let a_is_reflection = *a >= n;
+    let b_is_reflection = *b >= n;
+    let a_rot = a.rem_euclid(n);
+    let b_rot = b.rem_euclid(n);
+
+    match (a_is_reflection, b_is_reflection) {
+        (false, false) => (a_rot + b_rot).rem_euclid(n),
+        (false, true) => n + (a_rot + b_rot).rem_euclid(n),
+        (true, false) => n + (a_rot - b_rot).rem_euclid(n),
+        (true, true) => (a_rot - b_rot).rem_euclid(n),
+    }
+}
+
+/// 20! is the largest factorial that fits in a 64-bit `usize`; beyond this,
+/// `permutation_size_for` stops growing `k` rather than overflowing.
+const MAX_PERMUTATION_SIZE: usize = 20;
+
+fn factorial(n: usize) -> usize {
+    (1..=n).product::<usize>().max(1)
+}
+
+fn permutation_size_for(modulus: usize) -> usize {
+    let mut k = 1;
+    while k < MAX_PERMUTATION_SIZE && factorial(k) < modulus.max(1) {
+        k += 1;
+    }
+    k
+}
+
+fn permutation_from_index(mut index: usize, k: usize) -> Vec<usize> {
+    let mut items: Vec<usize> = (0..k).collect();
+    let mut perm = Vec::with_capacity(k);
+    for i in (1..=k).rev() {
+        let f = factorial(i - 1);
+        let pos = (index / f).min(items.len() - 1);
+        index %= f;
+        perm.push(items.remove(pos));
+    }
+    perm
+}
+
+fn index_from_permutation(perm: &[usize]) -> usize {
+    let k = perm.len();
+    let mut items: Vec<usize> = (0..k).collect();
+    let mut index = 0;
+    for (i, &value) in perm.iter().enumerate() {
+        let pos = items.iter().position(|&x| x == value).unwrap_or(0);
+        index += pos * factorial(k - i - 1);
+        items.remove(pos);
+    }
+    index
+}